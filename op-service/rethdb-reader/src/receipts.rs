@@ -2,15 +2,28 @@
 //! [reth] database.
 
 use anyhow::{anyhow, Result};
+use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+use hasher::HasherKeccak;
 use reth_blockchain_tree::noop::NoopBlockchainTree;
 use reth_db::open_db_read_only;
+use reth_evm_optimism::l1::extract_l1_info;
 use reth_primitives::{
-    BlockHashOrNumber, Receipt, TransactionKind, TransactionMeta, TransactionSigned, MAINNET, U128,
-    U256, U64,
+    BlockHashOrNumber, Bytes, Chain, ChainSpec, Receipt, TransactionKind, TransactionMeta,
+    TransactionSigned, TxType, B256, BASE_MAINNET, BASE_SEPOLIA, MAINNET, OP_MAINNET, OP_SEPOLIA,
+    SEPOLIA, U128, U256, U64,
 };
-use reth_provider::{providers::BlockchainProvider, BlockReader, ProviderFactory, ReceiptProvider};
+use reth_provider::{
+    providers::BlockchainProvider, BlockReader, ProviderFactory, ReceiptProvider,
+    TransactionsProvider,
+};
+use reth_revm::revm::{
+    optimism::L1BlockInfo,
+    primitives::{utils::calc_blob_gasprice, SpecId},
+};
+use reth_rlp::Encodable;
 use reth_rpc_types::{Log, TransactionReceipt};
-use std::{ffi::c_char, path::Path};
+use serde::{Deserialize, Serialize};
+use std::{ffi::c_char, path::Path, sync::Arc};
 
 /// A [ReceiptsResult] is a wrapper around a JSON string containing serialized [TransactionReceipt]s
 /// as well as an error status that is compatible with FFI.
@@ -45,6 +58,35 @@ impl ReceiptsResult {
     }
 }
 
+/// The amount of data gas a single blob consumes, per EIP-4844.
+const DATA_GAS_PER_BLOB: u64 = 131_072;
+
+/// Resolves a numeric chain ID to the [ChainSpec] this node should read receipts against.
+///
+/// The chain spec is needed for more than bookkeeping: fork activation info determines
+/// whether a receipt carries a pre-Byzantium post-state root or a status code, and governs
+/// typed-transaction and base-fee semantics, all of which differ between mainnet, its
+/// testnets, and the OP-stack networks built on top of it.
+///
+/// Chains with their own well-known spec get it verbatim. Any other chain ID is assumed to
+/// be an OP-stack chain (an app-chain or devnet, say) that isn't one of the handful with a
+/// dedicated constant in this crate; those inherit the canonical OP-stack hardfork schedule
+/// from [OP_MAINNET] with only the chain ID swapped in, rather than being rejected outright.
+fn chain_spec_by_id(chain_id: u64) -> Result<Arc<ChainSpec>> {
+    Ok(match chain_id {
+        1 => MAINNET.clone(),
+        11_155_111 => SEPOLIA.clone(),
+        10 => OP_MAINNET.clone(),
+        11_155_420 => OP_SEPOLIA.clone(),
+        8453 => BASE_MAINNET.clone(),
+        84_532 => BASE_SEPOLIA.clone(),
+        _ => Arc::new(ChainSpec {
+            chain: Chain::from(chain_id),
+            ..(*OP_MAINNET).clone()
+        }),
+    })
+}
+
 /// Read the receipts for a blockhash from the RETH database directly.
 ///
 /// # Safety
@@ -54,6 +96,7 @@ impl ReceiptsResult {
 pub(crate) unsafe fn read_receipts_inner(
     block_hash: *const u8,
     block_hash_len: usize,
+    chain_id: u64,
     db_path: *const c_char,
 ) -> Result<ReceiptsResult> {
     // Convert the raw pointer and length back to a Rust slice
@@ -74,8 +117,9 @@ pub(crate) unsafe fn read_receipts_inner(
     }
     .to_str()?;
 
+    let chain_spec = chain_spec_by_id(chain_id)?;
     let db = open_db_read_only(Path::new(db_path_str), None).map_err(|e| anyhow!(e))?;
-    let factory = ProviderFactory::new(db, MAINNET.clone());
+    let factory = ProviderFactory::new(db, chain_spec.clone());
 
     // Create a read-only BlockChainProvider
     let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default())?;
@@ -91,6 +135,19 @@ pub(crate) unsafe fn read_receipts_inner(
     let block_number = block.number;
     let base_fee = block.base_fee_per_gas;
     let block_hash = block.hash_slow();
+    let excess_blob_gas = block.excess_blob_gas;
+    let spec_id = reth_revm::env::revm_spec(
+        &chain_spec,
+        reth_primitives::Head {
+            number: block_number,
+            timestamp: block.timestamp,
+            ..Default::default()
+        },
+    );
+    let is_byzantium = chain_spec.is_byzantium_active_at_block(block_number);
+    // The L1 attributes deposit transaction at the start of the block records the L1 base
+    // fee and scalars every other transaction's L1 data fee is computed from.
+    let l1_block_info = extract_l1_info(&block).ok();
     let receipts = block
         .body
         .into_iter()
@@ -103,9 +160,17 @@ pub(crate) unsafe fn read_receipts_inner(
                 block_hash,
                 block_number,
                 base_fee,
-                excess_blob_gas: None,
+                excess_blob_gas,
             };
-            build_transaction_receipt_with_block_receipts(tx, meta, receipt, &receipts)
+            build_transaction_receipt_with_block_receipts(
+                tx,
+                meta,
+                receipt,
+                &receipts,
+                l1_block_info.as_ref(),
+                spec_id,
+                is_byzantium,
+            )
         })
         .collect::<Option<Vec<_>>>()
         .ok_or(anyhow!("Failed to build receipts"))?;
@@ -123,7 +188,518 @@ pub(crate) unsafe fn read_receipts_inner(
     Ok(res)
 }
 
-/// Builds a hydrated [TransactionReceipt] from information in the passed transaction,
+/// Read the hydrated receipt for a single transaction, looked up by its hash, from the RETH
+/// database directly.
+///
+/// # Safety
+/// - All possible nil pointer dereferences are checked, and the function will return a
+///   failing [ReceiptsResult] if any are found.
+#[inline(always)]
+pub(crate) unsafe fn read_receipt_by_tx_hash_inner(
+    tx_hash: *const u8,
+    tx_hash_len: usize,
+    chain_id: u64,
+    db_path: *const c_char,
+) -> Result<ReceiptsResult> {
+    // Convert the raw pointer and length back to a Rust slice
+    let tx_hash: [u8; 32] = {
+        if tx_hash.is_null() {
+            anyhow::bail!("tx_hash pointer is null");
+        }
+        std::slice::from_raw_parts(tx_hash, tx_hash_len)
+    }
+    .try_into()?;
+
+    // Convert the *const c_char to a Rust &str
+    let db_path_str = {
+        if db_path.is_null() {
+            anyhow::bail!("db path pointer is null");
+        }
+        std::ffi::CStr::from_ptr(db_path)
+    }
+    .to_str()?;
+
+    let chain_spec = chain_spec_by_id(chain_id)?;
+    let db = open_db_read_only(Path::new(db_path_str), None).map_err(|e| anyhow!(e))?;
+    let factory = ProviderFactory::new(db, chain_spec.clone());
+
+    // Create a read-only BlockChainProvider
+    let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default())?;
+
+    // Resolve the transaction hash to its enclosing block via reth's tx-hash-to-block index,
+    // rather than requiring the caller to already know which block the transaction landed in.
+    let tx_number = provider
+        .transaction_id(tx_hash.into())?
+        .ok_or(anyhow!("Failed to find a transaction with the given hash"))?;
+    let block_number = provider.transaction_block(tx_number)?.ok_or(anyhow!(
+        "Failed to find the block containing the transaction"
+    ))?;
+
+    let block = provider
+        .block_by_number(block_number)?
+        .ok_or(anyhow!("Failed to fetch block"))?;
+    let receipts = provider
+        .receipts_by_block(BlockHashOrNumber::Number(block_number))?
+        .ok_or(anyhow!("Failed to fetch block receipts"))?;
+
+    let tx_index = block
+        .body
+        .iter()
+        .position(|tx| tx.hash == tx_hash.into())
+        .ok_or(anyhow!("Failed to find transaction within its block"))?;
+
+    let base_fee = block.base_fee_per_gas;
+    let block_hash = block.hash_slow();
+    let spec_id = reth_revm::env::revm_spec(
+        &chain_spec,
+        reth_primitives::Head {
+            number: block_number,
+            timestamp: block.timestamp,
+            ..Default::default()
+        },
+    );
+    let is_byzantium = chain_spec.is_byzantium_active_at_block(block_number);
+    let l1_block_info = extract_l1_info(&block).ok();
+    let tx = block.body[tx_index].clone();
+    let receipt = receipts
+        .get(tx_index)
+        .cloned()
+        .ok_or(anyhow!("receipt index out of range"))?;
+    let meta = TransactionMeta {
+        tx_hash: tx.hash,
+        index: tx_index as u64,
+        block_hash,
+        block_number,
+        base_fee,
+        excess_blob_gas: block.excess_blob_gas,
+    };
+    let hydrated_receipt = build_transaction_receipt_with_block_receipts(
+        tx,
+        meta,
+        receipt,
+        &receipts,
+        l1_block_info.as_ref(),
+        spec_id,
+        is_byzantium,
+    )
+    .ok_or(anyhow!("Failed to build receipt"))?;
+
+    // Convert the receipt to JSON for transport
+    let mut receipt_json = serde_json::to_string(&hydrated_receipt)?;
+
+    // Create a ReceiptsResult with a pointer to the json-ified receipt
+    let res = ReceiptsResult::success(receipt_json.as_mut_ptr() as *mut char, receipt_json.len());
+
+    // Forget the `receipt_json` string so that its memory isn't freed by the
+    // borrow checker at the end of this scope
+    std::mem::forget(receipt_json); // Prevent Rust from freeing the memory
+
+    Ok(res)
+}
+
+/// The concatenated, hydrated receipts for a contiguous block range, along with the index
+/// into `receipts` at which each block's receipts begin, so callers can split the JSON back
+/// up per block without re-parsing transaction indices.
+#[derive(Serialize, Deserialize)]
+struct RangeReceiptsResult {
+    /// The hydrated receipts for every block in the range, in block order.
+    receipts: Vec<OptimismTransactionReceipt>,
+    /// `block_offsets[i]` is the index into `receipts` of the first receipt belonging to the
+    /// `i`-th block in the requested range.
+    block_offsets: Vec<usize>,
+}
+
+/// Read the receipts for every block in `[start_block, end_block]` from the RETH database
+/// directly, opening the database and building the provider only once for the whole range.
+///
+/// # Safety
+/// - All possible nil pointer dereferences are checked, and the function will return a
+///   failing [ReceiptsResult] if any are found.
+#[inline(always)]
+pub(crate) unsafe fn read_receipts_range_inner(
+    start_block: u64,
+    end_block: u64,
+    chain_id: u64,
+    db_path: *const c_char,
+) -> Result<ReceiptsResult> {
+    anyhow::ensure!(
+        start_block <= end_block,
+        "start_block must be less than or equal to end_block"
+    );
+
+    // Convert the *const c_char to a Rust &str
+    let db_path_str = {
+        if db_path.is_null() {
+            anyhow::bail!("db path pointer is null");
+        }
+        std::ffi::CStr::from_ptr(db_path)
+    }
+    .to_str()?;
+
+    let chain_spec = chain_spec_by_id(chain_id)?;
+    let db = open_db_read_only(Path::new(db_path_str), None).map_err(|e| anyhow!(e))?;
+    let factory = ProviderFactory::new(db, chain_spec.clone());
+
+    // Create a read-only BlockChainProvider once and reuse it for every block in the range,
+    // rather than paying the setup cost of a fresh DB handle and provider per block.
+    let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default())?;
+
+    let mut receipts = Vec::new();
+    let mut block_offsets = Vec::with_capacity((end_block - start_block + 1) as usize);
+
+    for block_number in start_block..=end_block {
+        block_offsets.push(receipts.len());
+
+        let block = provider
+            .block_by_number(block_number)?
+            .ok_or(anyhow!("Failed to fetch block {block_number}"))?;
+        let block_receipts = provider
+            .receipts_by_block(BlockHashOrNumber::Number(block_number))?
+            .ok_or(anyhow!("Failed to fetch receipts for block {block_number}"))?;
+
+        let base_fee = block.base_fee_per_gas;
+        let block_hash = block.hash_slow();
+        let excess_blob_gas = block.excess_blob_gas;
+        let spec_id = reth_revm::env::revm_spec(
+            &chain_spec,
+            reth_primitives::Head {
+                number: block_number,
+                timestamp: block.timestamp,
+                ..Default::default()
+            },
+        );
+        let is_byzantium = chain_spec.is_byzantium_active_at_block(block_number);
+        let l1_block_info = extract_l1_info(&block).ok();
+
+        for (idx, (tx, receipt)) in block
+            .body
+            .into_iter()
+            .zip(block_receipts.clone())
+            .enumerate()
+        {
+            let meta = TransactionMeta {
+                tx_hash: tx.hash,
+                index: idx as u64,
+                block_hash,
+                block_number,
+                base_fee,
+                excess_blob_gas,
+            };
+            let receipt = build_transaction_receipt_with_block_receipts(
+                tx,
+                meta,
+                receipt,
+                &block_receipts,
+                l1_block_info.as_ref(),
+                spec_id,
+                is_byzantium,
+            )
+            .ok_or(anyhow!("Failed to build receipt in block {block_number}"))?;
+            receipts.push(receipt);
+        }
+    }
+
+    let result = RangeReceiptsResult {
+        receipts,
+        block_offsets,
+    };
+
+    // Convert the result to JSON for transport
+    let mut result_json = serde_json::to_string(&result)?;
+
+    // Create a ReceiptsResult with a pointer to the json-ified result
+    let res = ReceiptsResult::success(result_json.as_mut_ptr() as *mut char, result_json.len());
+
+    // Forget the `result_json` string so that its memory isn't freed by the
+    // borrow checker at the end of this scope
+    std::mem::forget(result_json); // Prevent Rust from freeing the memory
+
+    Ok(res)
+}
+
+/// A hydrated receipt bundled with a Merkle-Patricia inclusion proof against the block's
+/// `receipts_root`, so a light client can verify it without trusting the RPC.
+#[derive(Serialize, Deserialize)]
+struct ReceiptWithProof {
+    /// The hydrated receipt for the requested transaction.
+    receipt: OptimismTransactionReceipt,
+    /// The ordered list of RLP-encoded trie nodes along the path to the receipt's key.
+    proof: Vec<Bytes>,
+    /// The receipts root the proof was generated against, i.e. the block header's
+    /// `receipts_root`.
+    root: B256,
+}
+
+/// Read the receipt for a single transaction within a block, along with a Merkle-Patricia
+/// inclusion proof against the block's `receipts_root`, from the RETH database directly.
+///
+/// # Safety
+/// - All possible nil pointer dereferences are checked, and the function will return a
+///   failing [ReceiptsResult] if any are found.
+#[inline(always)]
+pub(crate) unsafe fn read_receipt_proof_inner(
+    block_hash: *const u8,
+    block_hash_len: usize,
+    tx_index: u64,
+    chain_id: u64,
+    db_path: *const c_char,
+) -> Result<ReceiptsResult> {
+    // Convert the raw pointer and length back to a Rust slice
+    let block_hash: [u8; 32] = {
+        if block_hash.is_null() {
+            anyhow::bail!("block_hash pointer is null");
+        }
+        std::slice::from_raw_parts(block_hash, block_hash_len)
+    }
+    .try_into()?;
+
+    // Convert the *const c_char to a Rust &str
+    let db_path_str = {
+        if db_path.is_null() {
+            anyhow::bail!("db path pointer is null");
+        }
+        std::ffi::CStr::from_ptr(db_path)
+    }
+    .to_str()?;
+
+    let chain_spec = chain_spec_by_id(chain_id)?;
+    let db = open_db_read_only(Path::new(db_path_str), None).map_err(|e| anyhow!(e))?;
+    let factory = ProviderFactory::new(db, chain_spec.clone());
+
+    // Create a read-only BlockChainProvider
+    let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default())?;
+
+    // Fetch the block and the receipts within it
+    let block = provider
+        .block_by_hash(block_hash.into())?
+        .ok_or(anyhow!("Failed to fetch block"))?;
+    let receipts = provider
+        .receipts_by_block(BlockHashOrNumber::Hash(block_hash.into()))?
+        .ok_or(anyhow!("Failed to fetch block receipts"))?;
+
+    let tx_index = tx_index as usize;
+    let tx = block
+        .body
+        .get(tx_index)
+        .cloned()
+        .ok_or(anyhow!("transaction index out of range"))?;
+    let receipt = receipts
+        .get(tx_index)
+        .cloned()
+        .ok_or(anyhow!("receipt index out of range"))?;
+
+    let block_number = block.number;
+    let base_fee = block.base_fee_per_gas;
+    let block_hash = block.hash_slow();
+    let spec_id = reth_revm::env::revm_spec(
+        &chain_spec,
+        reth_primitives::Head {
+            number: block_number,
+            timestamp: block.timestamp,
+            ..Default::default()
+        },
+    );
+    let is_byzantium = chain_spec.is_byzantium_active_at_block(block_number);
+    let l1_block_info = extract_l1_info(&block).ok();
+    let meta = TransactionMeta {
+        tx_hash: tx.hash,
+        index: tx_index as u64,
+        block_hash,
+        block_number,
+        base_fee,
+        excess_blob_gas: block.excess_blob_gas,
+    };
+    let hydrated_receipt = build_transaction_receipt_with_block_receipts(
+        tx,
+        meta,
+        receipt,
+        &receipts,
+        l1_block_info.as_ref(),
+        spec_id,
+        is_byzantium,
+    )
+    .ok_or(anyhow!("Failed to build receipt"))?;
+
+    // Build a keccak-hashed, memory-backed Patricia trie over every receipt in the block,
+    // keyed by the RLP-encoded transaction index, exactly as consensus does when computing
+    // the block header's `receipts_root`.
+    let memdb = Arc::new(MemoryDB::new(true));
+    let hasher = Arc::new(HasherKeccak::new());
+    let mut trie = PatriciaTrie::new(memdb, hasher);
+    for (idx, receipt) in receipts.iter().enumerate() {
+        let key = rlp_encode_index(idx as u64);
+        let mut value = Vec::new();
+        // Consensus encoding: typed receipts are the type byte followed by the RLP list,
+        // without the RLP string header the network encoding wraps them in.
+        receipt.clone().with_bloom().encode_inner(&mut value, false);
+        trie.insert(key, value)
+            .map_err(|e| anyhow!("failed to insert receipt into trie: {e:?}"))?;
+    }
+
+    let root = trie
+        .root()
+        .map_err(|e| anyhow!("failed to compute receipts root: {e:?}"))?;
+    anyhow::ensure!(
+        root == block.header.receipts_root.as_slice(),
+        "computed receipts root does not match the block header's receipts_root"
+    );
+
+    let proof = trie
+        .get_proof(&rlp_encode_index(tx_index as u64))
+        .map_err(|e| anyhow!("failed to generate receipt inclusion proof: {e:?}"))?
+        .into_iter()
+        .map(Bytes::from)
+        .collect();
+
+    let result = ReceiptWithProof {
+        receipt: hydrated_receipt,
+        proof,
+        root: block.header.receipts_root,
+    };
+
+    // Convert the result to JSON for transport
+    let mut result_json = serde_json::to_string(&result)?;
+
+    // Create a ReceiptsResult with a pointer to the json-ified result
+    let res = ReceiptsResult::success(result_json.as_mut_ptr() as *mut char, result_json.len());
+
+    // Forget the `result_json` string so that its memory isn't freed by the
+    // borrow checker at the end of this scope
+    std::mem::forget(result_json); // Prevent Rust from freeing the memory
+
+    Ok(res)
+}
+
+/// RLP-encodes a transaction index for use as a receipts trie key, per consensus encoding
+/// (e.g. index `0` encodes as `0x80`).
+#[inline(always)]
+fn rlp_encode_index(index: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    index.encode(&mut buf);
+    buf
+}
+
+/// The OP-stack fields `op-geth` attaches to `eth_getTransactionReceipt` responses that
+/// upstream reth's [TransactionReceipt] does not carry natively: deposit bookkeeping for
+/// type-0x7E transactions, and the L1 data fee for everything else.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OptimismTransactionReceiptFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deposit_nonce: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deposit_receipt_version: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_gas_used: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_gas_price: Option<U256>,
+    /// Pre-Ecotone only: the single scalar op-geth applied to the L1 base fee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_fee_scalar: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_fee: Option<U256>,
+    /// Ecotone and later: the scalar applied to the L1 base fee, replacing `l1_fee_scalar`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_base_fee_scalar: Option<U256>,
+    /// Ecotone and later: the L1 blob base fee the transaction was billed against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_blob_base_fee: Option<U256>,
+    /// Ecotone and later: the scalar applied to the L1 blob base fee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l1_blob_base_fee_scalar: Option<U256>,
+}
+
+/// A hydrated [TransactionReceipt] with the OP-stack fields flattened alongside it, so the
+/// emitted JSON matches what `op-geth` returns.
+#[derive(Serialize, Deserialize)]
+struct OptimismTransactionReceipt {
+    #[serde(flatten)]
+    inner: TransactionReceipt,
+    #[serde(flatten)]
+    op_fields: OptimismTransactionReceiptFields,
+}
+
+/// Computes the EIP-4844 blob gas fields for a single transaction.
+///
+/// Returns `(None, None)` for anything other than a type-3 transaction; `blob_gas_price`
+/// additionally depends on the block header carrying an `excess_blob_gas`, which is only
+/// populated from Cancun onward.
+fn build_blob_gas_fields(
+    tx: &TransactionSigned,
+    excess_blob_gas: Option<u64>,
+) -> (Option<U256>, Option<U128>) {
+    if tx.transaction.tx_type() != TxType::EIP4844 {
+        return (None, None);
+    }
+
+    let blob_count = tx
+        .transaction
+        .blob_versioned_hashes()
+        .map(|hashes| hashes.len() as u64)
+        .unwrap_or_default();
+    let blob_gas_used = Some(U256::from(blob_count * DATA_GAS_PER_BLOB));
+    let blob_gas_price =
+        excess_blob_gas.map(|excess_blob_gas| U128::from(calc_blob_gasprice(excess_blob_gas)));
+
+    (blob_gas_used, blob_gas_price)
+}
+
+/// Computes the OP-stack receipt fields for a single transaction.
+///
+/// Deposit transactions (type `0x7E`) carry their nonce and receipt-schema version straight
+/// from the stored receipt. Everything else is billed an L1 data fee, derived from the
+/// transaction's own calldata and the L1 block info recorded by the L1 attributes deposit
+/// transaction at the start of the block.
+fn build_op_receipt_fields(
+    tx: &TransactionSigned,
+    receipt: &Receipt,
+    l1_block_info: Option<&L1BlockInfo>,
+    spec_id: SpecId,
+) -> OptimismTransactionReceiptFields {
+    if tx.transaction.tx_type() == TxType::Deposit {
+        return OptimismTransactionReceiptFields {
+            deposit_nonce: receipt.deposit_nonce.map(U64::from),
+            deposit_receipt_version: receipt.deposit_receipt_version.map(U64::from),
+            ..Default::default()
+        };
+    }
+
+    let Some(l1_block_info) = l1_block_info else {
+        return OptimismTransactionReceiptFields::default();
+    };
+
+    let envelope = tx.envelope_encoded();
+    let l1_gas_used = l1_block_info.data_gas(&envelope, spec_id);
+    let l1_fee = l1_block_info.calculate_tx_l1_cost(&envelope, spec_id);
+
+    // Ecotone replaced the single `l1FeeScalar` with separate base-fee and blob-base-fee
+    // scalars, and started billing against a blob base fee alongside the L1 base fee; a
+    // populated `l1_blob_base_fee` is how `L1BlockInfo` signals that the block is Ecotone
+    // or later.
+    match l1_block_info.l1_blob_base_fee {
+        Some(l1_blob_base_fee) => OptimismTransactionReceiptFields {
+            l1_gas_used: Some(U256::from(l1_gas_used)),
+            l1_gas_price: Some(U256::from(l1_block_info.l1_base_fee)),
+            l1_fee: Some(U256::from(l1_fee)),
+            l1_base_fee_scalar: Some(l1_block_info.l1_base_fee_scalar),
+            l1_blob_base_fee: Some(l1_blob_base_fee),
+            l1_blob_base_fee_scalar: l1_block_info.l1_blob_base_fee_scalar,
+            ..Default::default()
+        },
+        None => {
+            let l1_fee_scalar = l1_block_info.l1_base_fee_scalar.to::<u64>() as f64 / 1_000_000.0;
+            OptimismTransactionReceiptFields {
+                l1_gas_used: Some(U256::from(l1_gas_used)),
+                l1_gas_price: Some(U256::from(l1_block_info.l1_base_fee)),
+                l1_fee_scalar: Some(l1_fee_scalar),
+                l1_fee: Some(U256::from(l1_fee)),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Builds a hydrated [OptimismTransactionReceipt] from information in the passed transaction,
 /// receipt, and block receipts.
 ///
 /// Returns [None] if the transaction's sender could not be recovered from the signature.
@@ -133,7 +709,10 @@ fn build_transaction_receipt_with_block_receipts(
     meta: TransactionMeta,
     receipt: Receipt,
     all_receipts: &[Receipt],
-) -> Option<TransactionReceipt> {
+    l1_block_info: Option<&L1BlockInfo>,
+    spec_id: SpecId,
+    is_byzantium: bool,
+) -> Option<OptimismTransactionReceipt> {
     let transaction = tx.clone().into_ecrecovered()?;
 
     // get the previous transaction cumulative gas used
@@ -160,14 +739,15 @@ fn build_transaction_receipt_with_block_receipts(
         logs: Vec::with_capacity(receipt.logs.len()),
         effective_gas_price: U128::from(transaction.effective_gas_price(meta.base_fee)),
         transaction_type: tx.transaction.tx_type().into(),
-        // TODO pre-byzantium receipts have a post-transaction state root
-        state_root: None,
+        // Pre-Byzantium receipts carry a post-transaction state root instead of a status
+        // code; post-Byzantium consensus encoding is the other way around.
+        state_root: (!is_byzantium).then_some(receipt.state_root).flatten(),
         logs_bloom: receipt.bloom_slow(),
-        status_code: if receipt.success {
-            Some(U64::from(1))
+        status_code: is_byzantium.then_some(if receipt.success {
+            U64::from(1)
         } else {
-            Some(U64::from(0))
-        },
+            U64::from(0)
+        }),
 
         // EIP-4844 fields
         blob_gas_price: None,
@@ -184,6 +764,13 @@ fn build_transaction_receipt_with_block_receipts(
         }
     }
 
+    (res_receipt.blob_gas_used, res_receipt.blob_gas_price) =
+        build_blob_gas_fields(&tx, meta.excess_blob_gas);
+
+    // Compute the OP-stack fields before consuming `receipt.logs` below, since
+    // `build_op_receipt_fields` needs to borrow `receipt` as a whole.
+    let op_fields = build_op_receipt_fields(&tx, &receipt, l1_block_info, spec_id);
+
     // get number of logs in the block
     let mut num_logs = 0;
     for prev_receipt in all_receipts.iter().take(meta.index as usize) {
@@ -205,7 +792,10 @@ fn build_transaction_receipt_with_block_receipts(
         res_receipt.logs.push(rpclog);
     }
 
-    Some(res_receipt)
+    Some(OptimismTransactionReceipt {
+        inner: res_receipt,
+        op_fields,
+    })
 }
 
 #[cfg(test)]
@@ -213,7 +803,8 @@ mod test {
     use super::*;
     use reth_db::database::Database;
     use reth_primitives::{
-        address, b256, bloom, Block, Bytes, Log, Receipts, SealedBlockWithSenders, TxType, U8,
+        address, b256, bloom, transaction::Signature, Address, Block, Bytes, Log, Receipts,
+        SealedBlockWithSenders, Transaction, TxDeposit, TxEip4844, TxType, U8,
     };
     use reth_provider::{BlockWriter, BundleStateWithReceipts, DatabaseProvider};
     use reth_revm::revm::db::BundleState;
@@ -233,10 +824,11 @@ mod test {
         let tx_sender = block.body[0]
             .recover_signer()
             .expect("failed to recover signer");
+        let first_block_hash = block.hash_slow();
 
         pr.append_blocks_with_bundle_state(
             vec![SealedBlockWithSenders {
-                block: block.seal_slow(),
+                block: block.clone().seal_slow(),
                 senders: vec![tx_sender],
             }],
             BundleStateWithReceipts::new(
@@ -245,6 +837,9 @@ mod test {
                     tx_type: TxType::EIP1559,
                     success: true,
                     cumulative_gas_used: 0x3aefc,
+                    state_root: None,
+                    deposit_nonce: None,
+                    deposit_receipt_version: None,
                     logs: vec![Log {
                         address: address!("4ce63f351597214ef0b9a319124eea9e0f9668bb"),
                         topics: vec![
@@ -264,8 +859,41 @@ mod test {
         )
         .expect("failed to append block and receipt to database");
 
+        // A second, distinct block chained onto the first, so range reads
+        // (`read_receipts_range_inner`) exercise `block_offsets` across more than a single
+        // trivial block.
+        let mut second_block = block.clone();
+        second_block.header.number = block_number + 1;
+        second_block.header.parent_hash = first_block_hash;
+        second_block.header.timestamp += 1;
+        second_block.body[0].hash =
+            b256!("2222222222222222222222222222222222222222222222222222222222222222");
+        let second_block_number = second_block.header.number;
+
+        pr.append_blocks_with_bundle_state(
+            vec![SealedBlockWithSenders {
+                block: second_block.clone().seal_slow(),
+                senders: vec![tx_sender],
+            }],
+            BundleStateWithReceipts::new(
+                BundleState::default(),
+                Receipts::from_block_receipt(vec![Receipt {
+                    tx_type: TxType::EIP1559,
+                    success: true,
+                    cumulative_gas_used: 0x3aefc,
+                    state_root: None,
+                    deposit_nonce: None,
+                    deposit_receipt_version: None,
+                    logs: vec![],
+                }]),
+                second_block_number,
+            ),
+            None,
+        )
+        .expect("failed to append second block and receipt to database");
+
         pr.commit()
-            .expect("failed to commit block and receipt to database");
+            .expect("failed to commit blocks and receipts to database");
     }
 
     #[test]
@@ -278,6 +906,7 @@ mod test {
             let receipts_res = super::read_receipts_inner(
                 block_hash.as_mut_ptr(),
                 32,
+                1,
                 CString::new("testdata/db").unwrap().into_raw() as *const c_char,
             )
             .unwrap();
@@ -329,4 +958,334 @@ mod test {
             crate::rdb_free_string(receipts_res.data as *mut c_char);
         }
     }
+
+    #[test]
+    fn fetch_receipt_proof() {
+        open_receipts_testdata_db();
+
+        unsafe {
+            let mut block_hash =
+                b256!("bcc3fb97b87bb4b14bacde74255cbfcf52675c0ad5e06fa264c0e5d6c0afd96e");
+            let proof_res = super::read_receipt_proof_inner(
+                block_hash.as_mut_ptr(),
+                32,
+                0,
+                1,
+                CString::new("testdata/db").unwrap().into_raw() as *const c_char,
+            )
+            .unwrap();
+
+            let proof_data =
+                std::slice::from_raw_parts(proof_res.data as *const u8, proof_res.data_len);
+            let result: super::ReceiptWithProof = serde_json::from_slice(proof_data).unwrap();
+
+            // The trie root the proof was built against already had to match the block
+            // header's `receipts_root` inside `read_receipt_proof_inner`, or this call
+            // would have returned an error instead of succeeding.
+            assert!(!result.proof.is_empty());
+            assert_eq!(result.receipt.inner.transaction_index, U64::from(0));
+
+            crate::rdb_free_string(proof_res.data as *mut c_char);
+        }
+    }
+
+    #[test]
+    fn fetch_receipt_by_tx_hash() {
+        open_receipts_testdata_db();
+
+        unsafe {
+            let mut tx_hash =
+                b256!("12c0074a4a7916fe6f39de8417fe93f1fa77bcadfd5fc31a317fb6c344f66602");
+            let receipt_res = super::read_receipt_by_tx_hash_inner(
+                tx_hash.as_mut_ptr(),
+                32,
+                1,
+                CString::new("testdata/db").unwrap().into_raw() as *const c_char,
+            )
+            .unwrap();
+
+            let receipt_data =
+                std::slice::from_raw_parts(receipt_res.data as *const u8, receipt_res.data_len);
+            let receipt: TransactionReceipt = serde_json::from_slice(receipt_data).unwrap();
+
+            assert_eq!(receipt.transaction_hash, Some(tx_hash));
+            assert_eq!(receipt.transaction_index, U64::from(0));
+            assert_eq!(receipt.block_number, Some(U256::from(9_942_861)));
+            assert_eq!(receipt.cumulative_gas_used, U256::from(241_404));
+
+            crate::rdb_free_string(receipt_res.data as *mut c_char);
+        }
+    }
+
+    #[test]
+    fn fetch_receipts_range() {
+        open_receipts_testdata_db();
+
+        unsafe {
+            let range_res = super::read_receipts_range_inner(
+                9_942_861,
+                9_942_862,
+                1,
+                CString::new("testdata/db").unwrap().into_raw() as *const c_char,
+            )
+            .unwrap();
+
+            let range_data =
+                std::slice::from_raw_parts(range_res.data as *const u8, range_res.data_len);
+            let result: super::RangeReceiptsResult = serde_json::from_slice(range_data).unwrap();
+
+            // Block 9_942_861 has one receipt and block 9_942_862 has one receipt, so the
+            // second block's receipts should start right where the first block's leave off.
+            assert_eq!(result.block_offsets, vec![0, 1]);
+            assert_eq!(result.receipts.len(), 2);
+            assert_eq!(result.receipts[0].inner.transaction_index, U64::from(0));
+            assert_eq!(
+                result.receipts[0].inner.block_number,
+                Some(U256::from(9_942_861))
+            );
+            assert_eq!(result.receipts[1].inner.transaction_index, U64::from(0));
+            assert_eq!(
+                result.receipts[1].inner.block_number,
+                Some(U256::from(9_942_862))
+            );
+
+            crate::rdb_free_string(range_res.data as *mut c_char);
+        }
+    }
+
+    #[test]
+    fn pre_byzantium_receipt_carries_no_status_code() {
+        open_receipts_testdata_db();
+
+        let db = reth_db::open_db_read_only(Path::new("testdata/db"), None).unwrap();
+        let factory = ProviderFactory::new(db, MAINNET.clone());
+        let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default()).unwrap();
+
+        let block_hash = b256!("bcc3fb97b87bb4b14bacde74255cbfcf52675c0ad5e06fa264c0e5d6c0afd96e");
+        let block = provider.block_by_hash(block_hash.into()).unwrap().unwrap();
+        let receipts = provider
+            .receipts_by_block(BlockHashOrNumber::Hash(block_hash.into()))
+            .unwrap()
+            .unwrap();
+
+        let tx = block.body[0].clone();
+        // Give the fixture receipt a real state root (the fixture itself stores `None`),
+        // so the assertion below actually proves `state_root` is plumbed through rather
+        // than just happening to already be `None`.
+        let state_root =
+            b256!("1111111111111111111111111111111111111111111111111111111111111111");
+        let receipt = Receipt {
+            state_root: Some(state_root),
+            ..receipts[0].clone()
+        };
+        let meta = TransactionMeta {
+            tx_hash: tx.hash,
+            index: 0,
+            block_hash: block.hash_slow(),
+            block_number: block.number,
+            base_fee: block.base_fee_per_gas,
+            excess_blob_gas: block.excess_blob_gas,
+        };
+
+        // Force the pre-Byzantium branch regardless of what the fixture's own chain spec
+        // says, to pin down the state-root/status-code swap on its own.
+        let hydrated = super::build_transaction_receipt_with_block_receipts(
+            tx,
+            meta,
+            receipt,
+            &receipts,
+            None,
+            SpecId::MERGE,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(hydrated.inner.status_code, None);
+        assert_eq!(hydrated.inner.state_root, Some(state_root));
+    }
+
+    #[test]
+    fn op_receipt_fields_deposit_tx_short_circuits_on_nonce() {
+        let deposit_tx = TransactionSigned {
+            hash: B256::ZERO,
+            signature: Signature::optimism_deposit_tx_signature(),
+            transaction: Transaction::Deposit(TxDeposit {
+                source_hash: B256::ZERO,
+                from: Address::ZERO,
+                to: TransactionKind::Call(Address::ZERO),
+                mint: None,
+                value: U256::ZERO,
+                gas_limit: 21_000,
+                is_system_transaction: false,
+                input: Bytes::default(),
+            }),
+        };
+        let receipt = Receipt {
+            tx_type: TxType::Deposit,
+            success: true,
+            cumulative_gas_used: 21_000,
+            state_root: None,
+            deposit_nonce: Some(7),
+            deposit_receipt_version: Some(1),
+            logs: vec![],
+        };
+
+        // A deposit transaction is never billed an L1 fee, so passing a populated
+        // `L1BlockInfo` should have no effect on the result.
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000_000),
+            ..Default::default()
+        };
+
+        let fields =
+            build_op_receipt_fields(&deposit_tx, &receipt, Some(&l1_block_info), SpecId::BEDROCK);
+
+        assert_eq!(fields.deposit_nonce, Some(U64::from(7)));
+        assert_eq!(fields.deposit_receipt_version, Some(U64::from(1)));
+        assert_eq!(fields.l1_fee, None);
+        assert_eq!(fields.l1_gas_used, None);
+    }
+
+    #[test]
+    fn op_receipt_fields_pre_ecotone_uses_single_fee_scalar() {
+        open_receipts_testdata_db();
+
+        let db = reth_db::open_db_read_only(Path::new("testdata/db"), None).unwrap();
+        let factory = ProviderFactory::new(db, MAINNET.clone());
+        let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default()).unwrap();
+        let block_hash = b256!("bcc3fb97b87bb4b14bacde74255cbfcf52675c0ad5e06fa264c0e5d6c0afd96e");
+        let block = provider.block_by_hash(block_hash.into()).unwrap().unwrap();
+        let receipts = provider
+            .receipts_by_block(BlockHashOrNumber::Hash(block_hash.into()))
+            .unwrap()
+            .unwrap();
+
+        let tx = block.body[0].clone();
+        let receipt = receipts[0].clone();
+
+        // `l1_blob_base_fee` unset is how `L1BlockInfo` signals a pre-Ecotone block.
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000_000),
+            l1_blob_base_fee: None,
+            l1_blob_base_fee_scalar: None,
+            ..Default::default()
+        };
+
+        let fields =
+            build_op_receipt_fields(&tx, &receipt, Some(&l1_block_info), SpecId::BEDROCK);
+
+        assert_eq!(fields.l1_fee_scalar, Some(1.0));
+        assert!(fields.l1_base_fee_scalar.is_none());
+        assert!(fields.l1_blob_base_fee.is_none());
+        assert!(fields.l1_blob_base_fee_scalar.is_none());
+        assert!(fields.l1_gas_used.is_some());
+        assert!(fields.l1_fee.is_some());
+    }
+
+    #[test]
+    fn op_receipt_fields_post_ecotone_uses_split_fee_scalars() {
+        open_receipts_testdata_db();
+
+        let db = reth_db::open_db_read_only(Path::new("testdata/db"), None).unwrap();
+        let factory = ProviderFactory::new(db, MAINNET.clone());
+        let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default()).unwrap();
+        let block_hash = b256!("bcc3fb97b87bb4b14bacde74255cbfcf52675c0ad5e06fa264c0e5d6c0afd96e");
+        let block = provider.block_by_hash(block_hash.into()).unwrap().unwrap();
+        let receipts = provider
+            .receipts_by_block(BlockHashOrNumber::Hash(block_hash.into()))
+            .unwrap()
+            .unwrap();
+
+        let tx = block.body[0].clone();
+        let receipt = receipts[0].clone();
+
+        // A populated `l1_blob_base_fee` is how `L1BlockInfo` signals the block is
+        // Ecotone or later.
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000_000),
+            l1_blob_base_fee: Some(U256::from(2_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(500_000)),
+            ..Default::default()
+        };
+
+        let fields =
+            build_op_receipt_fields(&tx, &receipt, Some(&l1_block_info), SpecId::ECOTONE);
+
+        assert_eq!(fields.l1_fee_scalar, None);
+        assert_eq!(fields.l1_base_fee_scalar, Some(U256::from(1_000_000)));
+        assert_eq!(fields.l1_blob_base_fee, Some(U256::from(2_000)));
+        assert_eq!(fields.l1_blob_base_fee_scalar, Some(U256::from(500_000)));
+        assert!(fields.l1_gas_used.is_some());
+        assert!(fields.l1_fee.is_some());
+    }
+
+    #[test]
+    fn blob_gas_fields_are_none_for_non_blob_transactions() {
+        open_receipts_testdata_db();
+
+        let db = reth_db::open_db_read_only(Path::new("testdata/db"), None).unwrap();
+        let factory = ProviderFactory::new(db, MAINNET.clone());
+        let provider = BlockchainProvider::new(factory, NoopBlockchainTree::default()).unwrap();
+        let block_hash = b256!("bcc3fb97b87bb4b14bacde74255cbfcf52675c0ad5e06fa264c0e5d6c0afd96e");
+        let block = provider.block_by_hash(block_hash.into()).unwrap().unwrap();
+        let tx = block.body[0].clone();
+
+        let (blob_gas_used, blob_gas_price) = build_blob_gas_fields(&tx, Some(1_000));
+
+        assert_eq!(blob_gas_used, None);
+        assert_eq!(blob_gas_price, None);
+    }
+
+    #[test]
+    fn blob_gas_fields_computed_from_blob_versioned_hashes_and_excess_blob_gas() {
+        let blob_tx = TransactionSigned {
+            hash: B256::ZERO,
+            signature: Signature::default(),
+            transaction: Transaction::Eip4844(TxEip4844 {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                max_fee_per_gas: 0,
+                max_priority_fee_per_gas: 0,
+                to: TransactionKind::Call(Address::ZERO),
+                value: U256::ZERO,
+                access_list: Default::default(),
+                blob_versioned_hashes: vec![B256::ZERO, B256::ZERO],
+                max_fee_per_blob_gas: 0,
+                input: Bytes::default(),
+            }),
+        };
+
+        let (blob_gas_used, blob_gas_price) = build_blob_gas_fields(&blob_tx, Some(1_000));
+
+        assert_eq!(blob_gas_used, Some(U256::from(2 * DATA_GAS_PER_BLOB)));
+        assert_eq!(blob_gas_price, Some(U128::from(calc_blob_gasprice(1_000))));
+
+        let (no_excess_gas_used, no_excess_gas_price) = build_blob_gas_fields(&blob_tx, None);
+        assert_eq!(no_excess_gas_used, Some(U256::from(2 * DATA_GAS_PER_BLOB)));
+        assert_eq!(no_excess_gas_price, None);
+    }
+
+    #[test]
+    fn chain_spec_by_id_resolves_op_mainnet() {
+        let spec = chain_spec_by_id(10).unwrap();
+        assert_eq!(spec.chain.id(), 10);
+    }
+
+    #[test]
+    fn chain_spec_by_id_falls_back_to_op_stack_defaults_for_unknown_chains() {
+        // An app-chain or devnet with its own chain ID isn't one of the handful of networks
+        // with a dedicated constant, so it should still resolve instead of erroring, using
+        // OP_MAINNET's hardfork schedule with the chain ID swapped in.
+        let spec = chain_spec_by_id(1_234_567).unwrap();
+
+        assert_eq!(spec.chain.id(), 1_234_567);
+        assert_eq!(
+            spec.is_byzantium_active_at_block(0),
+            OP_MAINNET.is_byzantium_active_at_block(0)
+        );
+    }
 }